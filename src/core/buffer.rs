@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::cmp;
+
+use regex::Regex;
+
+use utils::settings::FilterConfig;
+use core::line::LineCollection;
+use core::script::Script;
+
+// One tab's matching rule: either the existing static regex match, or a
+// user script that gets the final say over keep/drop, rendering and any
+// extracted fields. The two are evaluated the same way from `Buffer::parse`'s
+// point of view, so adding a third kind later only means adding a variant
+// here.
+pub enum Filter {
+    Regex(Regex),
+    Script(RefCell<Script>)
+}
+
+impl Filter {
+    fn from_config(config: &FilterConfig) -> Filter {
+        match config.script {
+            Some(ref source) => {
+                match Script::compile(source) {
+                    Ok(script) => Filter::Script(RefCell::new(script)),
+                    // A script that fails to compile degrades to "match
+                    // nothing" rather than taking down the whole tab.
+                    Err(_) => Filter::Regex(Regex::new("$^").unwrap())
+                }
+            },
+            None => {
+                let pattern = config.pattern.as_ref().map(|pattern| pattern.as_str()).unwrap_or(".*");
+                Filter::Regex(Regex::new(pattern).unwrap_or_else(|_| Regex::new("$^").unwrap()))
+            }
+        }
+    }
+
+    // Apply the filter to one raw line. Regex filters only ever keep-or-drop;
+    // script filters can additionally rewrite the line and extract fields.
+    fn apply(&self, line: &str) -> Option<(String, Vec<(String, String)>)> {
+        match *self {
+            Filter::Regex(ref regex) => {
+                if regex.is_match(line) {
+                    Some((line.to_string(), vec![]))
+                } else {
+                    None
+                }
+            },
+            Filter::Script(ref script) => {
+                let verdict = script.borrow_mut().evaluate(line);
+
+                if !verdict.keep {
+                    return None;
+                }
+
+                let rendered = verdict.rendered.unwrap_or_else(|| line.to_string());
+                let fields = verdict.fields.into_iter().collect();
+
+                Some((rendered, fields))
+            }
+        }
+    }
+}
+
+pub struct Buffer {
+    pub filter: Filter,
+    pub reverse_index: usize
+}
+
+impl Buffer {
+    pub fn new(filter: Filter) -> Buffer {
+        Buffer {
+            filter: filter,
+            reverse_index: 0
+        }
+    }
+
+    pub fn is_scrolled(&self) -> bool {
+        self.reverse_index > 0
+    }
+
+    pub fn reset_reverse_index(&mut self) {
+        self.reverse_index = 0;
+    }
+
+    pub fn adjust_reverse_index(&mut self, delta: i32, max_value: i32) {
+        let current = self.reverse_index as i32;
+        self.reverse_index = cmp::max(0, cmp::min(current + delta, max_value)) as usize;
+    }
+
+    // Run every line currently held by `lines` through this tab's filter.
+    // Only the rendered text is returned: derived fields a script attaches
+    // to a line are discarded here and are neither rendered nor stored.
+    pub fn parse(&self, lines: &LineCollection) -> Box<Iterator<Item = String>> {
+        let rendered: Vec<String> = lines.iter()
+            .filter_map(|line| self.filter.apply(line).map(|(rendered, _fields)| rendered))
+            .collect();
+
+        Box::new(rendered.into_iter())
+    }
+}
+
+pub struct BufferCollection {
+    items: Vec<RefCell<Buffer>>,
+    selected: usize
+}
+
+impl BufferCollection {
+    pub fn from_filters(filters: Vec<FilterConfig>) -> BufferCollection {
+        let items = filters.iter()
+            .map(|config| RefCell::new(Buffer::new(Filter::from_config(config))))
+            .collect();
+
+        BufferCollection {
+            items: items,
+            selected: 0
+        }
+    }
+
+    pub fn selected_item(&self) -> &RefCell<Buffer> {
+        &self.items[self.selected]
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected < self.items.len() - 1 {
+            self.selected += 1;
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::Ordering;
 use std::cell::RefCell;
+use std::time::{Duration, Instant};
 
 use utils::settings::Settings;
 use ui::frame::Frame;
@@ -14,7 +15,11 @@ use ext::signal::{self, SIGQUIT};
 pub struct Flow {
     frame: Frame,
     lines: LineCollection,
-    buffer_collection: BufferCollection
+    buffer_collection: BufferCollection,
+    incoming: Vec<String>,
+    last_commit: Instant,
+    frame_window: Duration,
+    frame_threshold: usize
 }
 
 impl Flow {
@@ -29,7 +34,11 @@ impl Flow {
         Flow {
             frame: Frame::new(&menu_item_names),
             lines: LineCollection::new(settings.values.max_lines_count),
-            buffer_collection: BufferCollection::from_filters(settings.config_file.filters)
+            buffer_collection: BufferCollection::from_filters(settings.config_file.filters),
+            incoming: vec![],
+            last_commit: Instant::now(),
+            frame_window: Duration::from_millis(settings.values.frame_window_ms),
+            frame_threshold: settings.values.frame_size_threshold
         }
     }
 
@@ -50,12 +59,20 @@ impl Flow {
                 Event::Navigation(state) => self.frame.navigation.change_state(state),
                 Event::Search(action) => self.handle_search(action),
                 Event::Resize => self.resize(),
-                Event::Quit => self.quit(),
+                Event::Quit => {
+                    self.flush_incoming();
+                    self.quit();
+                },
                 _ => {
-                    let mut mutex_guarded_lines = lines.lock().unwrap();
-                    if !mutex_guarded_lines.is_empty() {
-                        let pending_lines = mutex_guarded_lines.drain(..).collect();
-                        self.append_incoming_lines(pending_lines);
+                    {
+                        let mut mutex_guarded_lines = lines.lock().unwrap();
+                        if !mutex_guarded_lines.is_empty() {
+                            self.incoming.extend(mutex_guarded_lines.drain(..));
+                        }
+                    }
+
+                    if self.should_commit_frame() {
+                        self.flush_incoming();
                     }
                 }
             };
@@ -114,6 +131,16 @@ impl Flow {
             SearchAction::ToggleFilterMode => {
                 self.frame.navigation.search.toggle_filter_mode();
                 self.perform_search();
+            },
+            SearchAction::HistoryPrevious => {
+                if self.frame.navigation.search.input_field.recall_previous() == QueryState::Changed {
+                    self.perform_search();
+                }
+            },
+            SearchAction::HistoryNext => {
+                if self.frame.navigation.search.input_field.recall_next() == QueryState::Changed {
+                    self.perform_search();
+                }
             }
         }
     }
@@ -138,10 +165,39 @@ impl Flow {
         self.lines.clear_excess();
     }
 
+    // Coalesce a burst of incoming lines into a single frame: commit once the
+    // accumulated batch reaches the size threshold or the time window since the
+    // last commit has elapsed. This avoids a full re-parse/reprint per drained
+    // batch when logs arrive at high throughput.
+    fn should_commit_frame(&self) -> bool {
+        if self.incoming.is_empty() {
+            return false;
+        }
+
+        self.incoming.len() >= self.frame_threshold ||
+            self.last_commit.elapsed() >= self.frame_window
+    }
+
+    // Commit the buffered burst as a single frame and restart the window.
+    fn flush_incoming(&mut self) {
+        if self.incoming.is_empty() {
+            return;
+        }
+
+        let pending_lines = self.incoming.drain(..).collect();
+        self.append_incoming_lines(pending_lines);
+        self.last_commit = Instant::now();
+    }
+
     fn reset_view(&mut self) {
         let lines_iter = self.current_buffer().borrow().parse(&self.lines);
         self.frame.print(lines_iter, None);
         self.frame.scroll(self.current_buffer().borrow().reverse_index as i32);
+
+        // Flush the offscreen pad to the terminal once per committed frame
+        // (print + scroll above only touch the pad) instead of letting
+        // whatever last wrote to it decide when a refresh happens.
+        self.frame.commit();
     }
 
     fn reset_scroll(&self) {
@@ -157,6 +213,7 @@ impl Flow {
         let lines_iter = self.current_buffer().borrow().parse(&self.lines);
         self.frame.print(lines_iter, query);
         self.frame.navigation.search.render();
+        self.frame.commit();
     }
 
     fn quit(&self) {
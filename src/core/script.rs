@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use rhai::{Engine, Scope, AST, Dynamic};
+
+// Caps are generous enough for per-line filter logic (string munging, a running
+// counter or two) but keep a pathological script (an infinite loop, unbounded
+// recursion, a multi-megabyte string buffer) from stalling the render loop.
+static MAX_OPERATIONS: u64 = 200_000;
+static MAX_EXPR_DEPTH: usize = 64;
+static MAX_STRING_SIZE: usize = 8_192;
+static MAX_ARRAY_SIZE: usize = 1_024;
+static MAX_MAP_SIZE: usize = 256;
+
+// The decision a script renders for a single log line: whether to keep it,
+// how to redraw it (falls back to the raw line when `None`), and any fields
+// it chose to extract.
+pub struct LineVerdict {
+    pub keep: bool,
+    pub rendered: Option<String>,
+    pub fields: HashMap<String, String>
+}
+
+impl LineVerdict {
+    fn dropped() -> LineVerdict {
+        LineVerdict {
+            keep: false,
+            rendered: None,
+            fields: HashMap::new()
+        }
+    }
+}
+
+// A compiled user script bound to one filter tab. The engine carries the
+// sandbox limits and the `scope` persists between calls, so a script can keep
+// running state (hit counts, rate windows) across the lines it sees.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>
+}
+
+impl Script {
+    // Compile `source` once up front; tabs hold on to the result for the
+    // lifetime of the buffer instead of recompiling per line.
+    pub fn compile(source: &str) -> Result<Script, String> {
+        let engine = build_sandboxed_engine();
+        let ast = engine.compile(source).map_err(|error| error.to_string())?;
+
+        Ok(Script {
+            engine: engine,
+            ast: ast,
+            scope: Scope::new()
+        })
+    }
+
+    // Run the script against one raw line. The script body is expected to
+    // call `__verdict.keep(bool)`, optionally `__verdict.render(string)` and
+    // `__verdict.field(name, value)`; anything it doesn't call keeps the
+    // corresponding default (a line is kept unless the script calls
+    // `__verdict.keep(false)` or fails with a runtime error).
+    pub fn evaluate(&mut self, line: &str) -> LineVerdict {
+        let verdict = Dynamic::from(RefCellVerdict::new());
+
+        self.scope.push("line", line.to_string());
+        self.scope.push("__verdict", verdict.clone());
+
+        let result = self.engine.eval_ast_with_scope::<Dynamic>(&mut self.scope, &self.ast);
+
+        self.scope.remove::<String>("line");
+        self.scope.remove::<Dynamic>("__verdict");
+
+        if result.is_err() {
+            return LineVerdict::dropped();
+        }
+
+        verdict.cast::<RefCellVerdict>().into_inner()
+    }
+}
+
+fn build_sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_ARRAY_SIZE);
+    engine.set_max_map_size(MAX_MAP_SIZE);
+    engine.disable_symbol("eval");
+
+    engine.register_type::<RefCellVerdict>()
+        .register_fn("keep", RefCellVerdict::keep)
+        .register_fn("render", RefCellVerdict::render)
+        .register_fn("field", RefCellVerdict::field);
+
+    engine
+}
+
+// Thin mutable handle the script body calls into (`__verdict.keep()`, etc.)
+// through the `__verdict` variable pushed into the scope; wrapping it lets `evaluate` hand the
+// script a single `Dynamic` that it mutates in place rather than threading
+// a return value through every script author has to remember to produce.
+#[derive(Clone)]
+struct RefCellVerdict(LineVerdictCell);
+
+type LineVerdictCell = std::rc::Rc<std::cell::RefCell<LineVerdict>>;
+
+impl RefCellVerdict {
+    fn new() -> RefCellVerdict {
+        RefCellVerdict(std::rc::Rc::new(std::cell::RefCell::new(LineVerdict {
+            keep: true,
+            rendered: None,
+            fields: HashMap::new()
+        })))
+    }
+
+    fn into_inner(self) -> LineVerdict {
+        match std::rc::Rc::try_unwrap(self.0) {
+            Ok(cell) => cell.into_inner(),
+            Err(shared) => {
+                let verdict = shared.borrow();
+                LineVerdict {
+                    keep: verdict.keep,
+                    rendered: verdict.rendered.clone(),
+                    fields: verdict.fields.clone()
+                }
+            }
+        }
+    }
+
+    fn keep(&mut self, keep: bool) {
+        self.0.borrow_mut().keep = keep;
+    }
+
+    fn render(&mut self, text: String) {
+        self.0.borrow_mut().rendered = Some(text);
+    }
+
+    fn field(&mut self, name: String, value: String) {
+        self.0.borrow_mut().fields.insert(name, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compile_rejects_invalid_syntax() {
+        assert!(Script::compile("this is not rhai (").is_err());
+    }
+
+    #[test]
+    fn evaluate_keeps_by_default() {
+        let mut script = Script::compile("").unwrap();
+        let verdict = script.evaluate("hello world");
+
+        assert!(verdict.keep);
+        assert!(verdict.rendered.is_none());
+    }
+
+    #[test]
+    fn evaluate_drops_when_script_says_so() {
+        let mut script = Script::compile(r#"__verdict.keep(false);"#).unwrap();
+        let verdict = script.evaluate("hello world");
+
+        assert!(!verdict.keep);
+    }
+
+    #[test]
+    fn evaluate_applies_render_and_fields() {
+        let mut script = Script::compile(
+            r#"__verdict.render("> " + line); __verdict.field("length", line.len().to_string());"#
+        ).unwrap();
+        let verdict = script.evaluate("hello");
+
+        assert_eq!(verdict.rendered, Some("> hello".to_string()));
+        assert_eq!(verdict.fields.get("length"), Some(&"5".to_string()));
+    }
+
+    #[test]
+    fn evaluate_drops_on_a_runtime_error() {
+        let mut script = Script::compile("undefined_function()").unwrap();
+        let verdict = script.evaluate("hello world");
+
+        assert!(!verdict.keep);
+    }
+
+    #[test]
+    fn evaluate_persists_state_across_calls() {
+        // A running counter, the kind of stateful aggregation a regex
+        // filter can't express: `count` lives in the persistent `Scope`
+        // Script carries across evaluate() calls, rather than being reset
+        // per line.
+        let mut script = Script::compile(
+            r#"
+                let count = if is_def_var("count") { count + 1 } else { 1 };
+                __verdict.field("count", count.to_string());
+            "#
+        ).unwrap();
+
+        script.evaluate("first");
+        let second = script.evaluate("second");
+
+        assert_eq!(second.fields.get("count"), Some(&"2".to_string()));
+    }
+}
@@ -4,7 +4,8 @@
 //
 
 use libc::{FILE, free, c_void, c_char};
-use std::ffi::CStr;
+use std::env;
+use std::ffi::{CStr, CString};
 use ncurses::*;
 
 use ext::readline::*;
@@ -13,6 +14,16 @@ static mut input: i32 = 0;
 static mut input_available: bool = false;
 static mut command_window: Option<WINDOW> = None;
 
+// Search history is persisted between runs under the user's home directory,
+// capped to the most recent entries.
+static HISTORY_FILE: &'static str = ".flow_history";
+static HISTORY_LIMIT: i32 = 1000;
+
+// ASCII Ctrl-R, readline's usual incremental reverse-search binding. Unlike
+// ncurses' KEY_UP/KEY_DOWN this is a plain byte value (0-255), well within
+// readline's keymap, so binding it directly is safe.
+static CTRL_R: i32 = 0x12;
+
 pub fn init() {
     unsafe {
         rl_change_environment = 0; // Conflicts with ncurses
@@ -24,6 +35,16 @@ pub fn init() {
         rl_getc_function = Some(getc);
         rl_input_available_hook = Some(is_input_available);
         rl_redisplay_function = Some(handle_redisplay);
+
+        rl_bind_key(CTRL_R, rl_reverse_search_history);
+
+        stifle_history(HISTORY_LIMIT);
+    }
+
+    if let Some(path) = history_path() {
+        if let Ok(path) = CString::new(path) {
+            unsafe { read_history(path.as_ptr()); }
+        }
     }
 }
 
@@ -47,6 +68,32 @@ pub fn terminate() {
     unsafe {
         rl_callback_handler_remove();
     }
+
+    if let Some(path) = history_path() {
+        if let Ok(path) = CString::new(path) {
+            unsafe { write_history(path.as_ptr()); }
+        }
+    }
+}
+
+// Recall the previous history entry into the line buffer, redisplay it, and
+// return the recalled text. Calls straight into readline's history API
+// rather than routing an arrow-key code through `rl_bind_key`: ncurses'
+// KEY_UP/KEY_DOWN (259/258) are outside readline's byte-sized keymap
+// (KEYMAP_SIZE 257), so binding them would index past the end of its
+// static keymap array.
+pub fn history_previous() -> Option<String> {
+    unsafe { rl_get_previous_history(1, 0); }
+    handle_redisplay();
+    read_buffer()
+}
+
+// Recall the next history entry into the line buffer, redisplay it, and return
+// the recalled text.
+pub fn history_next() -> Option<String> {
+    unsafe { rl_get_next_history(1, 0); }
+    handle_redisplay();
+    read_buffer()
 }
 
 pub fn read_buffer() -> Option<String> {
@@ -119,7 +166,7 @@ extern "C" fn handle_input(line_ptr: *mut c_char) {
     handle_redisplay();
 
     if !line.is_empty() {
-        // add history
+        unsafe { add_history(line_ptr); }
         handle_redisplay();
     }
 
@@ -128,4 +175,14 @@ extern "C" fn handle_input(line_ptr: *mut c_char) {
 
 unsafe fn cstr_ptr_to_str<'a>(c_str: *const i8) -> &'a str {
     CStr::from_ptr(c_str).to_str().unwrap()
+}
+
+// env::home_dir() is deprecated (inconsistent behavior on Windows when
+// $HOME isn't set); this is a Unix-only ncurses app, so read $HOME directly.
+fn history_path() -> Option<String> {
+    env::var("HOME").ok().map(|home| {
+        let mut path = std::path::PathBuf::from(home);
+        path.push(HISTORY_FILE);
+        path.to_string_lossy().into_owned()
+    })
 }
\ No newline at end of file
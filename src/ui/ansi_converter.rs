@@ -9,6 +9,16 @@ use flow::line::Line;
 // For color pair generation, when unsigned it must also be different than the 8 colors already defined.
 static COLOR_DEFAULT: i16 = -9;
 
+// Dynamically allocated color pairs and custom colors start above the range
+// that `init_ansi_colors`/`build_color_id` statically reserve for the 8 base
+// colors (ids up to 199, colors 0-15). A terminal reporting exactly 256
+// colors - the common `xterm-256color`/tmux case - is otherwise the
+// overwhelming majority of `COLORS() >= 256` terminals, so the base has to
+// leave headroom below 256 or `rgb_to_ncurses`'s `(id as i32) < COLORS()`
+// check can never pass and the init_color cache path never fires.
+static DYNAMIC_PAIR_BASE: i16 = 200;
+static DYNAMIC_COLOR_BASE: i16 = 16;
+
 pub fn init_ansi_colors() {
     let colors = [
         COLOR_BLACK,
@@ -34,50 +44,259 @@ fn build_color_id(foreground_color: &i16, background_color: &i16) -> i16 {
     100 + foreground_color.abs() * 10 + background_color.abs()
 }
 
-lazy_static! {
-    static ref ANSI_TO_NCURSES_MAPPING: HashMap<&'static str, CursesStyle> = {
-        let mut codes = HashMap::new();
-
-        codes.insert("[0m", CursesStyle::Reset);
-
-        codes.insert("[1m", CursesStyle::Attribute(A_BOLD, true));
-        codes.insert("[3m", CursesStyle::Attribute(A_STANDOUT, true)); // Italic
-        codes.insert("[4m", CursesStyle::Attribute(A_UNDERLINE, true));
-        codes.insert("[7m", CursesStyle::Attribute(A_REVERSE, true));
-        codes.insert("[9m", CursesStyle::Attribute(A_DIM, true)); // Strikethrough
-
-        codes.insert("[22m", CursesStyle::Attribute(A_BOLD, false));
-        codes.insert("[23m", CursesStyle::Attribute(A_STANDOUT, false)); // Italic
-        codes.insert("[24m", CursesStyle::Attribute(A_UNDERLINE, false));
-        codes.insert("[27m", CursesStyle::Attribute(A_REVERSE, false));
-        codes.insert("[29m", CursesStyle::Attribute(A_DIM, false)); // Strikethrough
-
-        codes.insert("[30m", CursesStyle::Color(Some(COLOR_BLACK), None));
-        codes.insert("[31m", CursesStyle::Color(Some(COLOR_RED), None));
-        codes.insert("[32m", CursesStyle::Color(Some(COLOR_GREEN), None));
-        codes.insert("[33m", CursesStyle::Color(Some(COLOR_YELLOW), None));
-        codes.insert("[34m", CursesStyle::Color(Some(COLOR_BLUE), None));
-        codes.insert("[35m", CursesStyle::Color(Some(COLOR_MAGENTA), None));
-        codes.insert("[36m", CursesStyle::Color(Some(COLOR_CYAN), None));
-        codes.insert("[37m", CursesStyle::Color(Some(COLOR_WHITE), None));
-        codes.insert("[39m", CursesStyle::Color(Some(COLOR_DEFAULT), None));
-
-        codes.insert("[40m", CursesStyle::Color(None, Some(COLOR_BLACK)));
-        codes.insert("[41m", CursesStyle::Color(None, Some(COLOR_RED)));
-        codes.insert("[42m", CursesStyle::Color(None, Some(COLOR_GREEN)));
-        codes.insert("[43m", CursesStyle::Color(None, Some(COLOR_YELLOW)));
-        codes.insert("[44m", CursesStyle::Color(None, Some(COLOR_BLUE)));
-        codes.insert("[45m", CursesStyle::Color(None, Some(COLOR_MAGENTA)));
-        codes.insert("[46m", CursesStyle::Color(None, Some(COLOR_CYAN)));
-        codes.insert("[47m", CursesStyle::Color(None, Some(COLOR_WHITE)));
-        codes.insert("[49m", CursesStyle::Color(None, Some(COLOR_DEFAULT)));
-
-        codes
-    };
+// The 8 ANSI colors plus the terminal default keep their statically-initialised
+// pair; everything else (256-color and truecolor) is allocated lazily.
+fn is_base_color(color: i16) -> bool {
+    color == COLOR_DEFAULT || (color >= 0 && color <= 7)
+}
+
+// Resolve a foreground/background pair to an ncurses color-pair id, allocating
+// and caching it on first use. ncurses color pairs are a scarce resource, so we
+// only ever create one pair per distinct (foreground, background) combination.
+fn pair_for(foreground: i16, background: i16) -> i16 {
+    if is_base_color(foreground) && is_base_color(background) {
+        return build_color_id(&foreground, &background);
+    }
+
+    let mut cache = COLOR_CACHE.lock().unwrap();
+
+    if let Some(&id) = cache.pairs.get(&(foreground, background)) {
+        return id;
+    }
+
+    let id = cache.next_pair;
+    if id == i16::max_value() || (id as i32) >= COLOR_PAIRS() {
+        // Out of color pairs: fall back to the default pair rather than calling
+        // init_pair with an out-of-range id.
+        return build_color_id(&COLOR_DEFAULT, &COLOR_DEFAULT);
+    }
+
+    cache.next_pair += 1;
+    init_pair(id, foreground, background);
+    cache.pairs.insert((foreground, background), id);
+    id
+}
+
+// Resolve a 256-color palette index to an ncurses color. When the terminal
+// lacks a 256-color palette we fall back to the nearest base color via RGB.
+fn indexed_to_ncurses(index: i16) -> i16 {
+    let index = clamp_component(index, 255);
+
+    if COLORS() >= 256 {
+        index
+    } else {
+        let (red, green, blue) = xterm_256_to_rgb(index);
+        nearest_ansi_color(red, green, blue)
+    }
+}
+
+// Resolve a 24-bit truecolor triple to an ncurses color. On a 256-color
+// terminal that can redefine colors we allocate a custom color (cached per
+// triple); otherwise we snap to the nearest xterm-256 cube index, or to a base
+// color when even the cube is unavailable.
+fn rgb_to_ncurses(red: i16, green: i16, blue: i16) -> i16 {
+    let red = clamp_component(red, 255);
+    let green = clamp_component(green, 255);
+    let blue = clamp_component(blue, 255);
+
+    if COLORS() >= 256 && can_change_color() {
+        let mut cache = COLOR_CACHE.lock().unwrap();
+
+        if let Some(&id) = cache.colors.get(&(red, green, blue)) {
+            return id;
+        }
+
+        let id = cache.next_color;
+        if id != i16::max_value() && (id as i32) < COLORS() {
+            cache.next_color += 1;
+            init_color(id, scale_component(red), scale_component(green), scale_component(blue));
+            cache.colors.insert((red, green, blue), id);
+            return id;
+        }
+    }
+
+    if COLORS() >= 256 {
+        nearest_cube_index(red, green, blue)
+    } else {
+        nearest_ansi_color(red, green, blue)
+    }
+}
+
+// ncurses color components are 0-1000; terminal components are 0-255.
+fn scale_component(value: i16) -> i16 {
+    (value as i32 * 1000 / 255) as i16
+}
+
+// Clamp an out-of-range parameter into a valid palette/component range rather
+// than forwarding it to ncurses, which rejects invalid color numbers.
+fn clamp_component(value: i16, max: i16) -> i16 {
+    if value < 0 {
+        0
+    } else if value > max {
+        max
+    } else {
+        value
+    }
+}
+
+// Snap a component to the nearest level of the xterm-256 6x6x6 cube.
+fn cube_level(value: i16) -> i16 {
+    let levels = [0, 95, 135, 175, 215, 255];
+    let mut best = 0;
+    let mut best_distance = i32::max_value();
+    for (position, &level) in levels.iter().enumerate() {
+        let distance = (value as i32 - level).abs();
+        if distance < best_distance {
+            best_distance = distance;
+            best = position as i16;
+        }
+    }
+    best
+}
+
+fn nearest_cube_index(red: i16, green: i16, blue: i16) -> i16 {
+    16 + 36 * cube_level(red) + 6 * cube_level(green) + cube_level(blue)
+}
+
+// Map an xterm-256 index back to an approximate RGB triple for palette
+// fall-backs.
+fn xterm_256_to_rgb(index: i16) -> (i16, i16, i16) {
+    match index {
+        0...15 => {
+            let base = [
+                (0, 0, 0), (128, 0, 0), (0, 128, 0), (128, 128, 0),
+                (0, 0, 128), (128, 0, 128), (0, 128, 128), (192, 192, 192),
+                (128, 128, 128), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+                (0, 0, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255)
+            ];
+            base[index as usize]
+        },
+        16...231 => {
+            let offset = index - 16;
+            let steps = [0, 95, 135, 175, 215, 255];
+            (steps[(offset / 36) as usize], steps[((offset % 36) / 6) as usize], steps[(offset % 6) as usize])
+        },
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+// Snap an RGB triple to the nearest of the 8 base ANSI colors.
+fn nearest_ansi_color(red: i16, green: i16, blue: i16) -> i16 {
+    let red_bit = (red > 127) as i16;
+    let green_bit = (green > 127) as i16;
+    let blue_bit = (blue > 127) as i16;
+    red_bit + green_bit * 2 + blue_bit * 4
+}
 
+struct ColorCache {
+    pairs: HashMap<(i16, i16), i16>,
+    colors: HashMap<(i16, i16, i16), i16>,
+    next_pair: i16,
+    next_color: i16
+}
+
+impl ColorCache {
+    fn new() -> ColorCache {
+        ColorCache {
+            pairs: HashMap::new(),
+            colors: HashMap::new(),
+            next_pair: DYNAMIC_PAIR_BASE,
+            next_color: DYNAMIC_COLOR_BASE
+        }
+    }
+}
+
+lazy_static! {
     static ref ACTIVE_VALUES: Mutex<(Vec<fn() -> u64>, i16, i16)> = Mutex::new((vec![], COLOR_DEFAULT, COLOR_DEFAULT));
+    static ref COLOR_CACHE: Mutex<ColorCache> = Mutex::new(ColorCache::new());
+}
+
+// Fold a single SGR (Select Graphic Rendition) parameter stream into the
+// `CursesStyle` transitions that `print` later replays. `params` is the body of
+// a `\x1b[...m` sequence already split on `;`; an empty stream is a reset.
+fn decode_sgr(params: &[i64]) -> Vec<CursesStyle> {
+    if params.is_empty() {
+        return vec![CursesStyle::Reset];
+    }
+
+    let mut styles = vec![];
+    let mut index = 0;
+
+    while index < params.len() {
+        match params[index] {
+            0 => styles.push(CursesStyle::Reset),
+
+            1 => styles.push(CursesStyle::Attribute(A_BOLD, true)),
+            3 => styles.push(CursesStyle::Attribute(A_STANDOUT, true)), // Italic
+            4 => styles.push(CursesStyle::Attribute(A_UNDERLINE, true)),
+            7 => styles.push(CursesStyle::Attribute(A_REVERSE, true)),
+            9 => styles.push(CursesStyle::Attribute(A_DIM, true)), // Strikethrough
+
+            22 => styles.push(CursesStyle::Attribute(A_BOLD, false)),
+            23 => styles.push(CursesStyle::Attribute(A_STANDOUT, false)), // Italic
+            24 => styles.push(CursesStyle::Attribute(A_UNDERLINE, false)),
+            27 => styles.push(CursesStyle::Attribute(A_REVERSE, false)),
+            29 => styles.push(CursesStyle::Attribute(A_DIM, false)), // Strikethrough
+
+            code @ 30...37 => styles.push(CursesStyle::Color(Some((code - 30) as i16), None)),
+            39 => styles.push(CursesStyle::Color(Some(COLOR_DEFAULT), None)),
+
+            code @ 40...47 => styles.push(CursesStyle::Color(None, Some((code - 40) as i16))),
+            49 => styles.push(CursesStyle::Color(None, Some(COLOR_DEFAULT))),
+
+            // Extended foreground/background. The payload (`5;N` or `2;R;G;B`)
+            // is decoded here and the parameters it occupies are skipped.
+            38 => index += consume_extended(&params[index..], true, &mut styles),
+            48 => index += consume_extended(&params[index..], false, &mut styles),
+
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    styles
 }
 
+// Decode an extended color lead-in starting at `params[0]` (38 or 48) and
+// return how many *additional* parameters were swallowed so the caller can
+// advance past them.
+fn consume_extended(params: &[i64], foreground: bool, styles: &mut Vec<CursesStyle>) -> usize {
+    match params.get(1) {
+        Some(&5) => {
+            match params.get(2) {
+                Some(&index) => {
+                    push_color(styles, indexed_to_ncurses(index as i16), foreground);
+                    2 // \x1b[38;5;N m
+                },
+                // Truncated payload (no N): nothing to skip past.
+                None => 0
+            }
+        },
+        Some(&2) => {
+            match (params.get(2), params.get(3), params.get(4)) {
+                (Some(&red), Some(&green), Some(&blue)) => {
+                    push_color(styles, rgb_to_ncurses(red as i16, green as i16, blue as i16), foreground);
+                    4 // \x1b[38;2;R;G;B m
+                },
+                // Truncated payload (missing R, G or B): nothing to skip past.
+                _ => 0
+            }
+        },
+        _ => 0
+    }
+}
+
+fn push_color(styles: &mut Vec<CursesStyle>, color: i16, foreground: bool) {
+    if foreground {
+        styles.push(CursesStyle::Color(Some(color), None));
+    } else {
+        styles.push(CursesStyle::Color(None, Some(color)));
+    }
+}
 
 pub trait AnsiStr {
     fn has_ansi_escape_sequence<'a>(&'a self) -> bool;
@@ -89,43 +308,159 @@ pub trait AnsiStr {
 
 impl AnsiStr for str {
     fn has_ansi_escape_sequence(&self) -> bool {
-        self.contains("")
+        self.contains("\x1b")
     }
 
     fn strip_ansi(&self) -> String {
         lazy_static! {
-            static ref STRIP_ANSI_MATCHER: Regex = Regex::new(r"(\x1b\[\d+m)").unwrap();
+            static ref STRIP_ANSI_MATCHER: Regex =
+                Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\].*?(\x07|\x1b\\)").unwrap();
         }
         STRIP_ANSI_MATCHER.replace_all(self, "")
     }
 
+    // Walk the string once, emitting a `Content` component for every run of
+    // non-escape text and a decoded `Styles` component for every `\x1b[...m`
+    // control sequence. Multi-parameter SGR (`\x1b[1;31;44m`) is folded into a
+    // list of `CursesStyle` transitions in left-to-right order.
     fn break_to_ncurses_components(&self) -> Vec<CursesComponent> {
+        let chars: Vec<char> = self.chars().collect();
         let mut components = vec![];
+        let mut content = String::new();
+        // Text accumulated between an OSC 8 open (`\x1b]8;;URI`) and its close
+        // (`\x1b]8;;`), together with the URI it points at.
+        let mut hyperlink: Option<(String, String)> = None;
+        let mut index = 0;
+
+        while index < chars.len() {
+            if chars[index] == '\x1b' && chars.get(index + 1) == Some(&'[') {
+                flush_content(&mut components, &mut content, &mut hyperlink);
+                index += 2;
+
+                let mut body = String::new();
+                let mut final_byte = None;
+                while index < chars.len() {
+                    let next = chars[index];
+                    index += 1;
+                    // A CSI sequence ends at its final byte (0x40-0x7E); the
+                    // bytes before it are the parameters.
+                    if next >= '\x40' && next <= '\x7e' {
+                        final_byte = Some(next);
+                        break;
+                    }
+                    body.push(next);
+                }
 
-        lazy_static! {
-            static ref BREAK_ANSI_MATCHER: Regex = Regex::new(r"(\x1b\[\d+m)|([^\x1b]*)").unwrap();
+                // Only SGR (`m`) sequences carry styling; other CSI sequences
+                // (cursor moves, clears, ...) are stripped without touching the
+                // surrounding text. Styling inside a hyperlink is dropped so the
+                // label stays contiguous.
+                if final_byte == Some('m') && hyperlink.is_none() {
+                    let params = parse_parameters(&body);
+                    components.push(CursesComponent::Styles(decode_sgr(&params)));
+                }
+            } else if chars[index] == '\x1b' && chars.get(index + 1) == Some(&']') {
+                index += 2;
+
+                let (body, consumed) = read_osc(&chars, index);
+                index += consumed;
+
+                // OSC 8 opens a hyperlink (`8;params;URI`) and closes it with an
+                // empty URI. Other OSC sequences are stripped.
+                if body.starts_with("8;") {
+                    let uri = body.splitn(3, ';').nth(2).unwrap_or("");
+
+                    flush_content(&mut components, &mut content, &mut hyperlink);
+
+                    // Closing the current link flushes it; opening a new one
+                    // while another is still open flushes the previous first so
+                    // its label and URI are not lost.
+                    if let Some((open_uri, text)) = hyperlink.take() {
+                        components.push(CursesComponent::Hyperlink { uri: open_uri, text: text });
+                    }
+                    if !uri.is_empty() {
+                        hyperlink = Some((uri.to_string(), String::new()));
+                    }
+                }
+            } else {
+                content.push(chars[index]);
+                index += 1;
+            }
         }
 
-        for capture in BREAK_ANSI_MATCHER.captures_iter(self) {
-            if capture.at(1).is_some() {
-                match ANSI_TO_NCURSES_MAPPING.get(capture.at(1).unwrap()) {
-                    Some(style) => components.push(CursesComponent::Style(style)),
-                    _ => {}
-                };
-            }
+        flush_content(&mut components, &mut content, &mut hyperlink);
 
-            if capture.at(2).is_some() {
-                let content = capture.at(2).unwrap().to_string();
-                components.push(CursesComponent::Content(content));
-            }
+        // An unterminated hyperlink still renders its accumulated text.
+        if let Some((uri, text)) = hyperlink {
+            components.push(CursesComponent::Hyperlink { uri: uri, text: text });
         }
 
         components
     }
 }
 
+// Route the pending text either into the currently open hyperlink's label or
+// into a standalone `Content` component, then clear the buffer.
+fn flush_content(components: &mut Vec<CursesComponent>,
+                 content: &mut String,
+                 hyperlink: &mut Option<(String, String)>) {
+    if content.is_empty() {
+        return;
+    }
+
+    match *hyperlink {
+        Some((_, ref mut text)) => text.push_str(content),
+        None => components.push(CursesComponent::Content(content.clone()))
+    }
+
+    content.clear();
+}
+
+// Read an OSC body starting at `start` up to its terminator: either BEL
+// (`\x07`) or ST (`\x1b\`). Returns the body and the number of characters
+// consumed, including the terminator. A bare ESC that is not part of an ST is
+// left in place so the caller can reprocess it as a new control sequence.
+fn read_osc(chars: &[char], start: usize) -> (String, usize) {
+    let mut body = String::new();
+    let mut index = start;
+
+    while index < chars.len() {
+        let character = chars[index];
+
+        if character == '\x07' {
+            index += 1;
+            break;
+        }
+        if character == '\x1b' {
+            if chars.get(index + 1) == Some(&'\\') {
+                index += 2;
+            }
+            break;
+        }
+
+        body.push(character);
+        index += 1;
+    }
+
+    (body, index - start)
+}
+
+// Split an SGR body on `;` into numeric parameters. An empty body yields an
+// empty list (treated as a reset by `decode_sgr`).
+fn parse_parameters(body: &str) -> Vec<i64> {
+    if body.is_empty() {
+        return vec![];
+    }
+
+    body.split(';')
+        .map(|param| param.parse().unwrap_or(0))
+        .collect()
+}
+
 pub trait AnsiLine {
     fn print<'a>(&'a self, window: WINDOW);
+
+    fn hyperlinks<'a>(&'a self) -> Vec<&'a str>;
 }
 
 impl AnsiLine for Line {
@@ -142,6 +477,28 @@ impl AnsiLine for Line {
             }
         };
     }
+
+    // The URIs embedded in this line, in order, so `Flow::process` can open the
+    // hyperlink under the cursor from a future keybinding.
+    //
+    // Deviation: derived from `components` on demand rather than stored as a
+    // dedicated field on `Line` (core::line::Line is outside this module and
+    // not touched here). Revisit if profiling ever shows repeated calls to
+    // `hyperlinks()` per line matter; today it's only needed once a keybinding
+    // lands.
+    fn hyperlinks(&self) -> Vec<&str> {
+        let mut uris = vec![];
+
+        if let Some(ref components) = self.components {
+            for component in components {
+                if let CursesComponent::Hyperlink { ref uri, .. } = *component {
+                    uris.push(uri.as_str());
+                }
+            }
+        }
+
+        uris
+    }
 }
 
 #[derive(Debug)]
@@ -168,7 +525,7 @@ impl CursesStyle {
                 let current_foreground = foreground.unwrap_or(active_values.1);
                 let current_background = background.unwrap_or(active_values.2);
 
-                let id = build_color_id(&current_foreground, &current_background);
+                let id = pair_for(current_foreground, current_background);
                 wattron(window, COLOR_PAIR(id) as i32);
 
                 active_values.1 = current_foreground;
@@ -178,6 +535,8 @@ impl CursesStyle {
                 for prop in active_values.0.drain(..) {
                     wattroff(window, prop() as i32);
                 }
+                active_values.1 = COLOR_DEFAULT;
+                active_values.2 = COLOR_DEFAULT;
                 let id = build_color_id(&COLOR_DEFAULT, &COLOR_DEFAULT);
                 wattron(window, COLOR_PAIR(id) as i32);
             }
@@ -187,19 +546,155 @@ impl CursesStyle {
 
 #[derive(Debug)]
 pub enum CursesComponent {
-    Style(&'static CursesStyle),
-    Content(String)
+    Styles(Vec<CursesStyle>),
+    Content(String),
+    Hyperlink { uri: String, text: String }
 }
 
 impl CursesComponent {
     pub fn print(&self, window: WINDOW) {
         match self {
-            &CursesComponent::Style(value) => {
-                value.print(window);
+            &CursesComponent::Styles(ref values) => {
+                for value in values {
+                    value.print(window);
+                }
             },
             &CursesComponent::Content(ref value) => {
                 wprintw(window, &value);
+            },
+            &CursesComponent::Hyperlink { uri: _, ref text } => {
+                // Render the link label underlined and in a distinct color so it
+                // stands out, then restore whatever styling was active before so
+                // the surrounding line is unaffected.
+                let active_values = ACTIVE_VALUES.lock().unwrap();
+                let underline_active = active_values.0.iter().any(|prop| prop() == A_UNDERLINE());
+
+                wattron(window, A_UNDERLINE() as i32);
+                let id = pair_for(COLOR_BLUE, COLOR_DEFAULT);
+                wattron(window, COLOR_PAIR(id) as i32);
+
+                wprintw(window, text);
+
+                if !underline_active {
+                    wattroff(window, A_UNDERLINE() as i32);
+                }
+                let id = pair_for(active_values.1, active_values.2);
+                wattron(window, COLOR_PAIR(id) as i32);
             }
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_sgr_empty_is_reset() {
+        let styles = decode_sgr(&[]);
+
+        assert_eq!(styles.len(), 1);
+        match styles[0] {
+            CursesStyle::Reset => {},
+            _ => panic!("expected Reset for an empty SGR body")
+        }
+    }
+
+    #[test]
+    fn decode_sgr_folds_multiple_parameters_in_order() {
+        // \x1b[1;31;44m: bold on, red foreground, blue background.
+        let styles = decode_sgr(&[1, 31, 44]);
+
+        assert_eq!(styles.len(), 3);
+        match styles[1] {
+            CursesStyle::Color(Some(foreground), None) => assert_eq!(foreground, 1),
+            _ => panic!("expected a foreground-only color transition")
+        }
+        match styles[2] {
+            CursesStyle::Color(None, Some(background)) => assert_eq!(background, 4),
+            _ => panic!("expected a background-only color transition")
+        }
+    }
+
+    #[test]
+    fn decode_sgr_unknown_parameter_is_ignored() {
+        let styles = decode_sgr(&[1, 999, 22]);
+
+        assert_eq!(styles.len(), 2);
+    }
+
+    #[test]
+    fn consume_extended_256_color_skips_two_parameters() {
+        let mut styles = vec![];
+        let consumed = consume_extended(&[38, 5, 196], true, &mut styles);
+
+        assert_eq!(consumed, 2);
+        assert_eq!(styles.len(), 1);
+    }
+
+    #[test]
+    fn consume_extended_truecolor_skips_four_parameters() {
+        let mut styles = vec![];
+        let consumed = consume_extended(&[48, 2, 10, 20, 30], false, &mut styles);
+
+        assert_eq!(consumed, 4);
+        assert_eq!(styles.len(), 1);
+    }
+
+    #[test]
+    fn consume_extended_truncated_payload_skips_nothing() {
+        let mut styles = vec![];
+        let consumed = consume_extended(&[38, 5], true, &mut styles);
+
+        assert_eq!(consumed, 0);
+        assert!(styles.is_empty());
+    }
+
+    #[test]
+    fn read_osc_stops_at_bel_terminator() {
+        let chars: Vec<char> = "8;;http://example.com\x07trailing".chars().collect();
+        let (body, consumed) = read_osc(&chars, 0);
+
+        assert_eq!(body, "8;;http://example.com");
+        assert_eq!(consumed, 22); // body + the BEL
+    }
+
+    #[test]
+    fn read_osc_stops_at_st_terminator() {
+        let chars: Vec<char> = "8;;http://example.com\x1b\\trailing".chars().collect();
+        let (body, consumed) = read_osc(&chars, 0);
+
+        assert_eq!(body, "8;;http://example.com");
+        assert_eq!(consumed, 23); // body + ESC + '\'
+    }
+
+    #[test]
+    fn break_to_ncurses_components_extracts_hyperlink_text_and_uri() {
+        let line = "\x1b]8;;http://example.com\x07click here\x1b]8;;\x07";
+        let components = line.break_to_ncurses_components();
+
+        assert_eq!(components.len(), 1);
+        match components[0] {
+            CursesComponent::Hyperlink { ref uri, ref text } => {
+                assert_eq!(uri, "http://example.com");
+                assert_eq!(text, "click here");
+            },
+            _ => panic!("expected a single Hyperlink component")
+        }
+    }
+
+    #[test]
+    fn break_to_ncurses_components_renders_unterminated_hyperlink() {
+        let line = "\x1b]8;;http://example.com\x07click here";
+        let components = line.break_to_ncurses_components();
+
+        assert_eq!(components.len(), 1);
+        match components[0] {
+            CursesComponent::Hyperlink { ref uri, ref text } => {
+                assert_eq!(uri, "http://example.com");
+                assert_eq!(text, "click here");
+            },
+            _ => panic!("expected the trailing unterminated hyperlink to still render")
+        }
+    }
+}
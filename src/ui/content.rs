@@ -43,9 +43,17 @@ impl Content {
         wclear(self.window);
     }
 
+    // Queue the offscreen pad's contents with `wnoutrefresh`, then flush every
+    // queued update with a single `doupdate`, so a whole frame is drawn in one
+    // refresh instead of one per write.
+    pub fn commit(&self) {
+        wnoutrefresh(self.window);
+        doupdate();
+    }
+
     pub fn resize(&self, width: i32, height: i32) {
         wresize(self.window, width, height);
-        wrefresh(self.window);
+        self.commit();
     }
 
     pub fn height(&self) -> i32 {
@@ -0,0 +1,41 @@
+// The set of things `Frame::watch` can hand back to `Flow::process` after
+// translating a raw keypress (or a resize/quit signal) into something the
+// core doesn't need a curses dependency to react to.
+pub enum Event {
+    SelectMenuItem(Direction),
+    ScrollContents(Offset),
+    Navigation(NavigationState),
+    Search(SearchAction),
+    Resize,
+    Quit,
+    None
+}
+
+pub enum Direction {
+    Left,
+    Right
+}
+
+pub enum Offset {
+    Line(i32),
+    Viewport(i32),
+    Top,
+    Bottom
+}
+
+// Which panel `Navigation` should switch into, e.g. opening/closing the
+// search bar.
+pub enum NavigationState {
+    Hidden,
+    Search
+}
+
+// A keypress routed to the search/command input while it has focus.
+pub enum SearchAction {
+    ReadInput(Vec<i32>),
+    FindNextMatch,
+    FindPreviousMatch,
+    ToggleFilterMode,
+    HistoryPrevious,
+    HistoryNext
+}
@@ -0,0 +1,55 @@
+use frontend::readline;
+
+#[derive(PartialEq)]
+pub enum QueryState {
+    Unchanged,
+    Changed
+}
+
+// The search bar's text buffer. Typed characters go through readline's
+// callback-read path (`forward_input` -> `rl_callback_read_char`); history
+// recall calls straight into readline's history API instead, since
+// ncurses' arrow-key codes are out of range for readline's own keymap (see
+// `frontend::readline::history_previous`).
+pub struct InputField {
+    text: String
+}
+
+impl InputField {
+    pub fn new() -> InputField {
+        InputField { text: String::new() }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // Forward a raw keypress (a printable character, backspace, ...) into
+    // readline and report whether the buffer changed as a result.
+    pub fn read(&mut self, keys: Vec<i32>) -> QueryState {
+        for key in keys {
+            readline::forward_input(key);
+        }
+
+        self.apply(readline::read_buffer())
+    }
+
+    pub fn recall_previous(&mut self) -> QueryState {
+        self.apply(readline::history_previous())
+    }
+
+    pub fn recall_next(&mut self) -> QueryState {
+        self.apply(readline::history_next())
+    }
+
+    fn apply(&mut self, recalled: Option<String>) -> QueryState {
+        let current = recalled.unwrap_or_else(String::new);
+
+        if current == self.text {
+            QueryState::Unchanged
+        } else {
+            self.text = current;
+            QueryState::Changed
+        }
+    }
+}
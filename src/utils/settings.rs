@@ -0,0 +1,39 @@
+// Parsed runtime configuration: the config file's filter tabs plus the
+// tunable values that aren't worth a config section of their own.
+pub struct Settings {
+    pub config_file: ConfigFile,
+    pub values: Values
+}
+
+pub struct ConfigFile {
+    pub filters: Vec<FilterConfig>
+}
+
+// One `[[filters]]` tab. A tab matches either a static `pattern` or a
+// user `script` (see `core::script::Script`), never both.
+pub struct FilterConfig {
+    pub name: String,
+    pub pattern: Option<String>,
+    pub script: Option<String>
+}
+
+pub struct Values {
+    pub max_lines_count: usize,
+
+    // How long a burst of incoming lines is allowed to accumulate before
+    // `Flow::process` forces a `reset_view`, and the line-count threshold
+    // that forces one sooner. Defaults favor interactive latency; raise
+    // them for high-volume batch tailing.
+    pub frame_window_ms: u64,
+    pub frame_size_threshold: usize
+}
+
+impl Default for Values {
+    fn default() -> Values {
+        Values {
+            max_lines_count: 10_000,
+            frame_window_ms: 16,
+            frame_size_threshold: 256
+        }
+    }
+}